@@ -0,0 +1,109 @@
+use std::ops::Range;
+
+use unicode_width::UnicodeWidthStr;
+
+/// Greedily word-wraps `graphemes` into rows at most `max_width` display
+/// columns wide, never splitting a word across rows. The space a row wraps
+/// on is swallowed (excluded from both ranges) just like a normal line
+/// break; a single word wider than `max_width` is hard-broken as a last
+/// resort. Returns one grapheme-index range per row.
+pub fn wrap_rows(graphemes: &[String], max_width: usize) -> Vec<Range<usize>> {
+    if graphemes.is_empty() {
+        return single_row(0..0);
+    }
+    if max_width == 0 {
+        return single_row(0..graphemes.len());
+    }
+
+    let mut rows = Vec::new();
+    let mut row_start = 0;
+    let mut col = 0;
+    let mut i = 0;
+
+    while i < graphemes.len() {
+        let w = graphemes[i].width();
+
+        if col + w > max_width && col > 0 {
+            match (row_start..i).rev().find(|&j| graphemes[j] == " ") {
+                Some(space_i) => {
+                    rows.push(row_start..space_i);
+                    row_start = space_i + 1;
+                }
+                None => {
+                    // a single word longer than the line: hard-break here
+                    rows.push(row_start..i);
+                    row_start = i;
+                }
+            }
+            col = graphemes[row_start..i].iter().map(|g| g.width()).sum();
+            continue;
+        }
+
+        col += w;
+        i += 1;
+    }
+
+    rows.push(row_start..graphemes.len());
+    rows
+}
+
+// `vec![range]` trips clippy::single_range_in_vec_init, which assumes a
+// single-range vec is almost always a typo'd `vec![start..end]` where a
+// plain `Range` was meant
+fn single_row(row: Range<usize>) -> Vec<Range<usize>> {
+    std::iter::once(row).collect()
+}
+
+/// Index of the row containing grapheme `i` (the last row if `i` is at or
+/// past the end of the text, e.g. once the test is complete).
+pub fn row_of(rows: &[Range<usize>], i: usize) -> usize {
+    for (idx, row) in rows.iter().enumerate() {
+        if i < row.end || idx == rows.len() - 1 {
+            return idx;
+        }
+    }
+    0
+}
+
+/// Scrolls so that `active_row` stays within the visible window, keeping it
+/// roughly centred rather than jumping straight to an edge.
+pub fn scroll_for(active_row: usize, total_rows: usize, visible_rows: usize) -> usize {
+    if visible_rows == 0 || total_rows <= visible_rows {
+        return 0;
+    }
+
+    let centered = active_row.saturating_sub(visible_rows / 2);
+    centered.min(total_rows - visible_rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graphemes(text: &str) -> Vec<String> {
+        text.chars().map(String::from).collect()
+    }
+
+    #[test]
+    fn wraps_on_word_boundaries() {
+        let g = graphemes("the quick brown fox");
+        let rows = wrap_rows(&g, 10);
+
+        let rendered: Vec<String> = rows.iter().map(|r| g[r.clone()].concat()).collect();
+        assert_eq!(rendered, vec!["the quick", "brown fox"]);
+    }
+
+    #[test]
+    fn hard_breaks_a_word_longer_than_the_line() {
+        let g = graphemes("supercalifragilistic");
+        let rows = wrap_rows(&g, 5);
+        assert!(rows.iter().all(|r| r.end - r.start <= 5));
+    }
+
+    #[test]
+    fn row_of_clamps_to_last_row_at_end_of_text() {
+        let g = graphemes("the quick brown fox");
+        let rows = wrap_rows(&g, 10);
+        assert_eq!(row_of(&rows, g.len()), rows.len() - 1);
+    }
+}
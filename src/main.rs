@@ -1,19 +1,28 @@
+mod backend;
+mod layout;
+mod results;
+mod text_source;
+
 use std::{
     collections::{HashMap, HashSet},
-    io::{self, stdout, Stdout, Write},
+    io,
+    path::PathBuf,
     time::Instant,
 };
 
 use crossterm::{
-    cursor::{MoveToNextLine, MoveToPreviousLine},
-    event::{read, Event, KeyCode, KeyEventKind},
-    execute, queue,
-    style::{Color, Print, SetBackgroundColor, SetForegroundColor},
-    terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType},
+    event::{KeyCode, KeyEventKind},
+    style::Color,
+    terminal::{disable_raw_mode, enable_raw_mode},
 };
+use unicode_segmentation::UnicodeSegmentation;
+
+use backend::{Backend, CrosstermBackend, InputEvent};
+use results::Results;
+use text_source::{GenerateOptions, TextSource};
 
-fn str_index(string: &str, i: usize) -> char {
-    (string.as_bytes()[i]) as char
+fn graphemes_of(text: &str) -> Vec<String> {
+    text.graphemes(true).map(String::from).collect()
 }
 
 struct Formatter {
@@ -22,42 +31,62 @@ struct Formatter {
 }
 
 impl Formatter {
-    fn apply_fg(&mut self, stdout: &mut Stdout, color: Color) {
+    fn apply_fg(&mut self, backend: &mut dyn Backend, color: Color) {
         if self.fg_color != color {
             self.fg_color = color;
-            queue!(stdout, SetForegroundColor(self.fg_color)).unwrap();
+            backend.set_fg(color);
         }
     }
 
-    fn apply_bg(&mut self, stdout: &mut Stdout, color: Color) {
+    fn apply_bg(&mut self, backend: &mut dyn Backend, color: Color) {
         if self.bg_color != color {
             self.bg_color = color;
-            queue!(stdout, SetBackgroundColor(self.bg_color)).unwrap();
+            backend.set_bg(color);
         }
     }
 }
 
-#[derive(Debug)]
 struct State {
     start: Option<Instant>,
-    text: String,
+    // target text, segmented into grapheme clusters so multibyte glyphs
+    // (accents, CJK, emoji) are never sliced in half
+    graphemes: Vec<String>,
     i: usize,
 
     // are these structures overkill?
     mismatches: HashSet<usize>,
     extensions: HashMap<usize, String>,
     skips: HashMap<usize, usize>,
+
+    // every char handled, and the subset of those that were wrong, feed
+    // raw/net wpm and accuracy on the results screen
+    total_keystrokes: usize,
+    error_keystrokes: usize,
+    // instantaneous wpm, sampled once a second, feeds the consistency score
+    wpm_samples: Vec<f64>,
+    last_sample_at: Option<Instant>,
+    last_sample_words: usize,
+
+    // index of the first visible wrapped row, recomputed every render so the
+    // active row stays on screen as the text scrolls
+    scroll: usize,
 }
 
 impl State {
     fn new(text: &str) -> Self {
         Self {
             start: None,
-            text: text.to_string(),
+            graphemes: graphemes_of(text),
             i: 0,
             mismatches: HashSet::new(),
             extensions: HashMap::new(),
             skips: HashMap::new(),
+            total_keystrokes: 0,
+            error_keystrokes: 0,
+            wpm_samples: Vec::new(),
+            last_sample_at: None,
+            last_sample_words: 0,
+            scroll: 0,
         }
     }
 
@@ -70,38 +99,62 @@ impl State {
         // - match and increment index
 
         if self.start.is_none() {
-            self.start = Some(Instant::now());
+            let now = Instant::now();
+            self.start = Some(now);
+            // seed so the first sample waits for a full interval instead of
+            // firing immediately at ~0s elapsed (which reads as ~infinite wpm)
+            self.last_sample_at = Some(now);
         }
+        self.total_keystrokes += 1;
 
-        let target_c = str_index(&self.text, self.i);
+        let target_g = self.graphemes[self.i].as_str();
 
-        if c == ' ' && target_c != ' ' {
+        if c == ' ' && target_g != " " {
             let mut next_word_i = self.i;
-            while str_index(&self.text, next_word_i) != ' ' && next_word_i < self.text.len() - 1 {
+            while self.graphemes[next_word_i] != " " && next_word_i < self.graphemes.len() - 1 {
                 next_word_i += 1;
             }
             self.skips.insert(next_word_i, self.i);
             self.i = next_word_i + 1;
-            return;
-        }
-
-        if let Some(extension) = self.extensions.get_mut(&self.i) {
+            self.error_keystrokes += 1;
+        } else if let Some(extension) = self.extensions.get_mut(&self.i) {
             extension.push(c);
-            return;
-        }
-
-        if target_c == c {
+            self.error_keystrokes += 1;
+        } else if target_g.chars().eq(std::iter::once(c)) {
             self.i += 1;
-            return;
+        } else if target_g == " " {
+            self.extensions.insert(self.i, c.to_string());
+            self.error_keystrokes += 1;
+        } else {
+            self.mismatches.insert(self.i);
+            self.i += 1;
+            self.error_keystrokes += 1;
         }
 
-        if target_c == ' ' {
-            self.extensions.insert(self.i, c.to_string());
+        self.maybe_sample_wpm();
+    }
+
+    // samples instantaneous wpm once a second while the test is running, for
+    // the consistency score on the results screen. this is words typed since
+    // the *previous* sample over the interval elapsed, not the cumulative
+    // words-so-far/total-elapsed average `get_wpm` reports, which barely
+    // moves once a run is underway and would make consistency meaningless
+    fn maybe_sample_wpm(&mut self) {
+        let now = Instant::now();
+        let Some(last_sample_at) = self.last_sample_at else {
+            return;
+        };
+        let elapsed = now.duration_since(last_sample_at).as_secs_f64();
+        if elapsed < 1.0 {
             return;
         }
 
-        self.mismatches.insert(self.i);
-        self.i += 1;
+        let words_now = self.words_typed();
+        let delta_words = words_now.saturating_sub(self.last_sample_words);
+        self.wpm_samples.push(delta_words as f64 / elapsed * 60.);
+
+        self.last_sample_at = Some(now);
+        self.last_sample_words = words_now;
     }
 
     fn handle_backspace(&mut self) {
@@ -123,7 +176,7 @@ impl State {
             return;
         }
 
-        if str_index(&self.text, self.i - 1) == ' ' {
+        if self.graphemes[self.i - 1] == " " {
             if let Some(start) = self.skips.get(&(self.i - 1)) {
                 let temp_start = *start;
                 self.skips.remove(&(self.i - 1));
@@ -137,19 +190,58 @@ impl State {
     }
 
     fn should_exit(&self) -> bool {
-        self.i >= self.text.len()
+        self.i >= self.graphemes.len()
+    }
+
+    fn words_typed(&self) -> usize {
+        self.graphemes[0..self.i]
+            .join("")
+            .split_whitespace()
+            .count()
     }
 
     fn get_wpm(&self) -> Option<f64> {
         self.start.map(|start| {
-            self.text[0..self.i].split_whitespace().count() as f64
-                / Instant::now().duration_since(start).as_secs_f64()
-                * 60.
+            self.words_typed() as f64 / Instant::now().duration_since(start).as_secs_f64() * 60.
         })
     }
 
-    fn render(&self) {
-        let mut stdout = stdout();
+    fn get_results(&self) -> Results {
+        let elapsed = self
+            .start
+            .map_or(0.0, |start| Instant::now().duration_since(start).as_secs_f64());
+        results::compute(
+            self.total_keystrokes,
+            self.error_keystrokes,
+            elapsed,
+            &self.wpm_samples,
+        )
+    }
+
+    fn render_results(&self, backend: &mut dyn Backend) {
+        let results = self.get_results();
+        backend.clear_all();
+        backend.move_to(0, 0);
+        backend.print("Results");
+        backend.move_to(0, 1);
+        backend.print(&format!("net wpm: {:.2}", results.net_wpm));
+        backend.move_to(0, 2);
+        backend.print(&format!("raw wpm: {:.2}", results.raw_wpm));
+        backend.move_to(0, 3);
+        backend.print(&format!("accuracy: {:.1}%", results.accuracy));
+        backend.move_to(0, 4);
+        backend.print(&format!("consistency: {:.0}", results.consistency));
+        backend.flush();
+    }
+
+    fn render(&mut self, backend: &mut dyn Backend) {
+        let (cols, term_rows) = backend.size().unwrap_or((80, 24));
+        let rows = layout::wrap_rows(&self.graphemes, cols.max(1) as usize);
+        let active_row = layout::row_of(&rows, self.i);
+        // row 0 is reserved for the "N wpm" status line
+        let visible_rows = term_rows.saturating_sub(1).max(1) as usize;
+        self.scroll = layout::scroll_for(active_row, rows.len(), visible_rows);
+
         let mut formatter = Formatter {
             fg_color: Color::Reset,
             bg_color: Color::Reset,
@@ -160,100 +252,163 @@ impl State {
             .map(|(end, start)| *start..=*end)
             .collect();
 
-        queue!(
-            stdout,
-            Clear(ClearType::CurrentLine),
-            MoveToPreviousLine(0),
-            Clear(ClearType::CurrentLine),
-            Print(match self.get_wpm() {
-                Some(wpm) => format!("{:.2} wpm", wpm),
-                None => "Start typing".to_string(),
-            }),
-            MoveToNextLine(0)
-        )
-        .unwrap();
-        formatter.apply_fg(&mut stdout, Color::Green);
+        backend.clear_all();
+        backend.move_to(0, 0);
+        backend.print(&match self.get_wpm() {
+            Some(wpm) => format!("{:.2} wpm", wpm),
+            None => "Start typing".to_string(),
+        });
 
-        for (i, c) in self.text.chars().enumerate() {
-            if let Some(extension) = self.extensions.get(&i) {
-                formatter.apply_fg(&mut stdout, Color::Red);
-                formatter.apply_bg(&mut stdout, Color::Reset);
+        for (row_idx, row) in rows.iter().enumerate().skip(self.scroll).take(visible_rows) {
+            backend.move_to(0, 1 + (row_idx - self.scroll) as u16);
+            formatter.apply_fg(backend, Color::Green);
 
-                queue!(stdout, Print(extension), Print(c)).unwrap();
-                continue;
-            }
+            for i in row.clone() {
+                let g = self.graphemes[i].as_str();
+
+                if let Some(extension) = self.extensions.get(&i) {
+                    formatter.apply_fg(backend, Color::Red);
+                    formatter.apply_bg(backend, Color::Reset);
 
-            if i < self.i {
-                if self.mismatches.contains(&i)
-                    || skip_ranges.iter().any(|range| range.contains(&i))
-                {
-                    formatter.apply_fg(&mut stdout, Color::Red);
-                } else {
-                    formatter.apply_fg(&mut stdout, Color::Green);
+                    backend.print(extension);
+                    backend.print(g);
+                    continue;
+                }
+
+                if i < self.i {
+                    if self.mismatches.contains(&i)
+                        || skip_ranges.iter().any(|range| range.contains(&i))
+                    {
+                        formatter.apply_fg(backend, Color::Red);
+                    } else {
+                        formatter.apply_fg(backend, Color::Green);
+                    }
+                    formatter.apply_bg(backend, Color::Reset);
+                    backend.print(g);
+                } else if i == self.i {
+                    formatter.apply_fg(backend, Color::Black);
+                    formatter.apply_bg(backend, Color::White);
+                    backend.print(g);
+                } else if i > self.i {
+                    formatter.apply_fg(backend, Color::Reset);
+                    formatter.apply_bg(backend, Color::Reset);
+                    backend.print(g);
                 }
-                formatter.apply_bg(&mut stdout, Color::Reset);
-                queue!(stdout, Print(c)).unwrap();
-            } else if i == self.i {
-                formatter.apply_fg(&mut stdout, Color::Black);
-                formatter.apply_bg(&mut stdout, Color::White);
-                queue!(stdout, Print(c)).unwrap();
-            } else if i > self.i {
-                formatter.apply_fg(&mut stdout, Color::Reset);
-                formatter.apply_bg(&mut stdout, Color::Reset);
-                queue!(stdout, Print(c)).unwrap();
             }
         }
 
-        formatter.apply_fg(&mut stdout, Color::Reset);
-        formatter.apply_bg(&mut stdout, Color::Reset);
-
-        stdout.flush().unwrap();
-    }
+        formatter.apply_fg(backend, Color::Reset);
+        formatter.apply_bg(backend, Color::Reset);
 
-    fn debug_render(&self) {
-        let mut stdout = stdout();
-        execute!(stdout, Print(format!("{:?}", self)), MoveToNextLine(1)).unwrap();
+        backend.flush();
     }
 }
 
-fn mainloop() -> io::Result<()> {
-    let text = "The quick brown fox jumped over the lazy wolves.";
+fn mainloop(backend: &mut dyn Backend, text: &str) -> io::Result<()> {
     let mut state = State::new(text);
-    state.render();
+    state.render(backend);
     loop {
-        match read()? {
-            Event::Key(event) => {
-                if event.kind != KeyEventKind::Press {
-                    continue;
-                }
-                match event.code {
-                    KeyCode::Backspace => {
-                        state.handle_backspace();
-                    }
-                    KeyCode::Char(c) => {
-                        state.handle_char(c);
-                    }
-                    KeyCode::Esc => {
-                        break;
-                    }
-                    _ => {}
-                }
+        let event = match backend.read_event()? {
+            InputEvent::Resize(_, _) => {
+                state.render(backend);
+                continue;
+            }
+            InputEvent::Key(event) => event,
+        };
+
+        if event.kind != KeyEventKind::Press {
+            continue;
+        }
+        match event.code {
+            KeyCode::Backspace => {
+                state.handle_backspace();
+            }
+            KeyCode::Char(c) => {
+                state.handle_char(c);
+            }
+            KeyCode::Esc => {
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        state.render(backend);
+
+        if state.should_exit() {
+            break;
+        }
+    }
+    state.render_results(backend);
+    Ok(())
+}
+
+/// Parses `quote mode` (a file path) or `random words mode` (`--words`,
+/// `--seed`, `--punctuation`, `--numbers`) from the command line.
+fn parse_args() -> TextSource {
+    let args: Vec<String> = std::env::args().skip(1).collect();
 
-                // state.debug_render();
-                state.render();
+    if let Some(path) = args.first() {
+        if !path.starts_with("--") {
+            return TextSource::File(PathBuf::from(path));
+        }
+    }
 
-                if state.should_exit() {
-                    break;
+    let mut options = GenerateOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--words" => {
+                if let Some(n) = iter.next().and_then(|s| s.parse().ok()) {
+                    options.word_count = n;
                 }
             }
+            "--seed" => {
+                if let Some(seed) = iter.next().and_then(|s| s.parse().ok()) {
+                    options.seed = Some(seed);
+                }
+            }
+            "--punctuation" => options.punctuation = true,
+            "--numbers" => options.numbers = true,
             _ => {}
         }
     }
-    Ok(())
+    TextSource::Random(options)
 }
+
 fn main() -> io::Result<()> {
+    let text = parse_args().load()?;
+
     enable_raw_mode()?;
-    let _ = mainloop();
+    let mut backend = CrosstermBackend::new();
+    let _ = mainloop(&mut backend, &text);
     disable_raw_mode()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend::RecordingBackend;
+
+    #[test]
+    fn render_colors_correct_mismatch_and_pending_chars() {
+        let mut state = State::new("cat");
+        state.handle_char('c');
+        state.handle_char('x'); // mismatch: target is 'a'
+
+        let mut backend = RecordingBackend::default();
+        state.render(&mut backend);
+
+        // cells[0] is the "N wpm" status line; the typed text follows it
+        let cells: Vec<_> = backend
+            .cells
+            .iter()
+            .skip(1)
+            .map(|cell| (cell.text.as_str(), cell.fg))
+            .collect();
+
+        assert_eq!(cells[0], ("c", Color::Green));
+        assert_eq!(cells[1], ("a", Color::Red));
+        assert_eq!(cells[2], ("t", Color::Black));
+    }
+}
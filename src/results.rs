@@ -0,0 +1,58 @@
+/// Summary statistics shown on the results screen once a test completes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Results {
+    pub net_wpm: f64,
+    pub raw_wpm: f64,
+    pub accuracy: f64,
+    pub consistency: f64,
+}
+
+/// `total_keystrokes`/`error_keystrokes` drive raw/net wpm and accuracy;
+/// `wpm_samples` is the once-a-second instantaneous-wpm buffer used for
+/// `consistency`.
+pub fn compute(
+    total_keystrokes: usize,
+    error_keystrokes: usize,
+    elapsed_secs: f64,
+    wpm_samples: &[f64],
+) -> Results {
+    let minutes = elapsed_secs / 60.0;
+    let correct_keystrokes = total_keystrokes.saturating_sub(error_keystrokes);
+
+    let accuracy = if total_keystrokes == 0 {
+        0.0
+    } else {
+        correct_keystrokes as f64 / total_keystrokes as f64 * 100.0
+    };
+
+    Results {
+        net_wpm: wpm(correct_keystrokes, minutes),
+        raw_wpm: wpm(total_keystrokes, minutes),
+        accuracy,
+        consistency: consistency(wpm_samples),
+    }
+}
+
+fn wpm(keystrokes: usize, minutes: f64) -> f64 {
+    if minutes <= 0.0 {
+        0.0
+    } else {
+        (keystrokes as f64 / 5.0) / minutes
+    }
+}
+
+// coefficient of variation of the sampled instantaneous wpm, inverted and
+// scaled to 0-100 so a perfectly steady pace scores 100
+fn consistency(samples: &[f64]) -> f64 {
+    if samples.len() < 2 {
+        return 100.0;
+    }
+
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    if mean <= 0.0 {
+        return 0.0;
+    }
+
+    let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+    (100.0 * (1.0 - variance.sqrt() / mean)).clamp(0.0, 100.0)
+}
@@ -0,0 +1,103 @@
+use std::{fs, io, path::PathBuf};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// A bundled list of common English words, used to generate random practice
+/// text when no quote file is given. Frequency-ordered lists make for more
+/// natural-feeling practice than uniformly random words, but for now this is
+/// a flat sample.
+const WORD_LIST: &[&str] = &[
+    "the", "of", "and", "a", "to", "in", "is", "you", "that", "it", "he", "was", "for", "on",
+    "are", "as", "with", "his", "they", "at", "be", "this", "from", "have", "or", "one", "had",
+    "by", "word", "but", "not", "what", "all", "were", "we", "when", "your", "can", "said",
+    "there", "use", "an", "each", "which", "she", "do", "how", "their", "if", "will", "up",
+    "other", "about", "out", "many", "then", "them", "these", "so", "some", "her", "would",
+    "make", "like", "him", "into", "time", "has", "look", "two", "more", "write", "go", "see",
+    "number", "no", "way", "could", "people", "my", "than", "first", "water", "been", "call",
+    "who", "oil", "its", "now", "find", "long", "down", "day", "did", "get", "come", "made",
+    "may", "part",
+];
+
+/// Where the practice text for a run comes from.
+pub enum TextSource {
+    /// A passage read from a file, split into words and rejoined so trailing
+    /// newlines/indentation don't become part of the typed text.
+    File(PathBuf),
+    /// A randomized sequence of words drawn from `WORD_LIST`.
+    Random(GenerateOptions),
+}
+
+/// Knobs for [`TextSource::Random`]. A fixed `seed` makes the generated text
+/// reproducible, e.g. to share or benchmark a particular test.
+pub struct GenerateOptions {
+    pub word_count: usize,
+    pub punctuation: bool,
+    pub numbers: bool,
+    pub seed: Option<u64>,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        Self {
+            word_count: 25,
+            punctuation: false,
+            numbers: false,
+            seed: None,
+        }
+    }
+}
+
+impl TextSource {
+    pub fn load(&self) -> io::Result<String> {
+        let text = match self {
+            TextSource::File(path) => load_file(path)?,
+            TextSource::Random(options) => generate_random(options)?,
+        };
+
+        if text.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "practice text is empty",
+            ));
+        }
+
+        Ok(text)
+    }
+}
+
+fn load_file(path: &PathBuf) -> io::Result<String> {
+    let contents = fs::read_to_string(path)?;
+    // re-join on whitespace so line wrapping/indentation in the source file
+    // isn't baked into the practice text
+    Ok(contents.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+fn generate_random(options: &GenerateOptions) -> io::Result<String> {
+    if options.word_count == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "word count must be at least 1",
+        ));
+    }
+
+    let mut rng = match options.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let words: Vec<String> = (0..options.word_count)
+        .map(|_| {
+            if options.numbers && rng.gen_bool(0.1) {
+                return rng.gen_range(0..1000).to_string();
+            }
+
+            let mut word = WORD_LIST[rng.gen_range(0..WORD_LIST.len())].to_string();
+            if options.punctuation && rng.gen_bool(0.15) {
+                word.push(*[',', '.', '!', '?'].get(rng.gen_range(0..4)).unwrap());
+            }
+            word
+        })
+        .collect();
+
+    Ok(words.join(" "))
+}